@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use btleplug::api::{Central, Manager as _, Peripheral as _, ScanFilter, WriteType};
+use btleplug::platform::{Manager, Peripheral};
+use uuid::Uuid;
+
+/// Default write characteristic used by common BLE ESC/POS print modules.
+/// Vendors differ, so callers can override it via `ble_print_escpos`.
+const DEFAULT_WRITE_CHARACTERISTIC: &str = "49535343-1e4d-4bd9-ba61-23c647249616";
+
+/// Conservative default ATT payload size (MTU 185 minus the 3-byte ATT header)
+/// used when the caller doesn't know the peripheral's negotiated MTU.
+const DEFAULT_MTU_PAYLOAD: usize = 182;
+
+/// Largest ATT payload allowed by the BLE Core spec's max negotiable MTU (517).
+const MAX_MTU_PAYLOAD: usize = 514;
+
+/// Peripherals are wrapped per-entry so the map lock is only held for the
+/// lookup/insert itself; a long fragmented write to one peripheral doesn't
+/// block `ble_connect`/`ble_print_escpos` calls for any other peripheral.
+#[derive(Default)]
+pub struct BleManager {
+  peripherals: tokio::sync::Mutex<HashMap<String, Arc<Peripheral>>>,
+}
+
+#[derive(serde::Serialize)]
+pub struct BleDeviceDto {
+  name: Option<String>,
+  address: String,
+  rssi: Option<i16>,
+}
+
+async fn first_adapter() -> Result<btleplug::platform::Adapter, String> {
+  let manager = Manager::new()
+    .await
+    .map_err(|e| format!("Unable to initialize BLE manager: {e}"))?;
+  let adapters = manager
+    .adapters()
+    .await
+    .map_err(|e| format!("Unable to list BLE adapters: {e}"))?;
+  adapters
+    .into_iter()
+    .next()
+    .ok_or_else(|| "No BLE adapter found".to_string())
+}
+
+#[tauri::command]
+pub async fn ble_scan(scan_ms: Option<u64>) -> Result<Vec<BleDeviceDto>, String> {
+  let adapter = first_adapter().await?;
+  adapter
+    .start_scan(ScanFilter::default())
+    .await
+    .map_err(|e| format!("BLE scan failed to start: {e}"))?;
+
+  tokio::time::sleep(Duration::from_millis(scan_ms.unwrap_or(5_000))).await;
+
+  let peripherals = adapter.peripherals().await;
+
+  adapter
+    .stop_scan()
+    .await
+    .map_err(|e| format!("BLE scan failed to stop: {e}"))?;
+
+  let peripherals = peripherals.map_err(|e| format!("Unable to list BLE peripherals: {e}"))?;
+
+  let mut out = Vec::with_capacity(peripherals.len());
+  for peripheral in peripherals {
+    let props = peripheral
+      .properties()
+      .await
+      .map_err(|e| format!("Unable to read BLE peripheral properties: {e}"))?;
+    let Some(props) = props else { continue };
+
+    out.push(BleDeviceDto {
+      name: props.local_name,
+      address: peripheral.address().to_string(),
+      rssi: props.rssi,
+    });
+  }
+
+  Ok(out)
+}
+
+#[tauri::command]
+pub async fn ble_connect(
+  manager: tauri::State<'_, std::sync::Arc<BleManager>>,
+  address: String,
+) -> Result<(), String> {
+  let adapter = first_adapter().await?;
+  let peripherals = adapter
+    .peripherals()
+    .await
+    .map_err(|e| format!("Unable to list BLE peripherals: {e}"))?;
+
+  let mut target = None;
+  for peripheral in peripherals {
+    if peripheral.address().to_string() == address {
+      target = Some(peripheral);
+      break;
+    }
+  }
+  let peripheral = target.ok_or_else(|| format!("No BLE peripheral found at {address}"))?;
+
+  peripheral
+    .connect()
+    .await
+    .map_err(|e| format!("BLE connect failed ({address}): {e}"))?;
+  peripheral
+    .discover_services()
+    .await
+    .map_err(|e| format!("BLE service discovery failed ({address}): {e}"))?;
+
+  manager
+    .peripherals
+    .lock()
+    .await
+    .insert(address, Arc::new(peripheral));
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn ble_print_escpos(
+  manager: tauri::State<'_, std::sync::Arc<BleManager>>,
+  address: String,
+  data: Vec<u8>,
+  characteristic_uuid: Option<String>,
+  mtu: Option<usize>,
+) -> Result<(), String> {
+  let peripheral = manager
+    .peripherals
+    .lock()
+    .await
+    .get(&address)
+    .cloned()
+    .ok_or_else(|| format!("No connected BLE peripheral {address}; call ble_connect first"))?;
+
+  let uuid_str = characteristic_uuid
+    .as_deref()
+    .unwrap_or(DEFAULT_WRITE_CHARACTERISTIC);
+  let uuid =
+    Uuid::parse_str(uuid_str).map_err(|e| format!("Invalid characteristic UUID {uuid_str}: {e}"))?;
+
+  let characteristic = peripheral
+    .characteristics()
+    .into_iter()
+    .find(|c| c.uuid == uuid)
+    .ok_or_else(|| format!("Characteristic {uuid_str} not found on {address}"))?;
+
+  let mtu_payload = mtu.unwrap_or(DEFAULT_MTU_PAYLOAD);
+  if mtu_payload == 0 || mtu_payload > MAX_MTU_PAYLOAD {
+    return Err(format!(
+      "Invalid mtu {mtu_payload}: expected 1-{MAX_MTU_PAYLOAD}"
+    ));
+  }
+
+  // ATT payloads are capped at the negotiated MTU, so fragment the stream
+  // and write-with-response so each fragment is acknowledged before the next.
+  for chunk in data.chunks(mtu_payload) {
+    peripheral
+      .write(&characteristic, chunk, WriteType::WithResponse)
+      .await
+      .map_err(|e| format!("BLE write failed ({address}): {e}"))?;
+  }
+
+  Ok(())
+}
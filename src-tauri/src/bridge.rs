@@ -0,0 +1,201 @@
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serialport::SerialPort;
+
+use crate::{apply_serial_settings, SerialSettingsDto};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// State for a single running bridge: its cancellation flag and listen port,
+/// plus the counters `bridge_status` reports. The serial port shared by every
+/// connected client is owned by the accept loop, not stored here.
+struct RunningBridge {
+  cancel: Arc<AtomicBool>,
+  listen_port: u16,
+  active_clients: Arc<AtomicUsize>,
+  bytes_forwarded: Arc<AtomicU64>,
+}
+
+#[derive(Default)]
+pub struct BridgeManager {
+  running: Mutex<Option<RunningBridge>>,
+}
+
+fn forward_client(
+  mut socket: TcpStream,
+  serial: Arc<Mutex<Box<dyn SerialPort>>>,
+  cancel: Arc<AtomicBool>,
+  active_clients: Arc<AtomicUsize>,
+  bytes_forwarded: Arc<AtomicU64>,
+) {
+  active_clients.fetch_add(1, Ordering::SeqCst);
+  let _ = socket.set_read_timeout(Some(POLL_INTERVAL));
+
+  let mut buf = [0u8; 512];
+  'client: while !cancel.load(Ordering::SeqCst) {
+    match socket.read(&mut buf) {
+      Ok(0) => break,
+      Ok(n) => {
+        let reply = {
+          let mut sp = serial.lock().unwrap();
+          for chunk in buf[..n].chunks(512) {
+            if sp.write_all(chunk).is_err() {
+              break 'client;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+          }
+          let _ = sp.flush();
+          bytes_forwarded.fetch_add(n as u64, Ordering::SeqCst);
+
+          // Relay back whatever status bytes the printer emits in reply,
+          // without blocking other clients on the port's 3s read timeout:
+          // only attempt a read if bytes are already sitting in the buffer.
+          match sp.bytes_to_read() {
+            Ok(available) if available > 0 => {
+              let mut buf = vec![0u8; (available as usize).min(64)];
+              match sp.read(&mut buf) {
+                Ok(reply_len) if reply_len > 0 => {
+                  buf.truncate(reply_len);
+                  Some(buf)
+                }
+                _ => None,
+              }
+            }
+            _ => None,
+          }
+        };
+
+        if let Some(reply) = reply {
+          let _ = socket.write_all(&reply);
+        }
+      }
+      Err(e) if e.kind() == std::io::ErrorKind::WouldBlock || e.kind() == std::io::ErrorKind::TimedOut => {}
+      Err(_) => break,
+    }
+  }
+
+  active_clients.fetch_sub(1, Ordering::SeqCst);
+}
+
+fn run_accept_loop(
+  listener: TcpListener,
+  serial: Arc<Mutex<Box<dyn SerialPort>>>,
+  cancel: Arc<AtomicBool>,
+  active_clients: Arc<AtomicUsize>,
+  bytes_forwarded: Arc<AtomicU64>,
+) {
+  let _ = listener.set_nonblocking(true);
+  while !cancel.load(Ordering::SeqCst) {
+    match listener.accept() {
+      Ok((socket, _addr)) => {
+        let serial = serial.clone();
+        let cancel = cancel.clone();
+        let active_clients = active_clients.clone();
+        let bytes_forwarded = bytes_forwarded.clone();
+        std::thread::spawn(move || {
+          forward_client(socket, serial, cancel, active_clients, bytes_forwarded);
+        });
+      }
+      Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+        std::thread::sleep(POLL_INTERVAL);
+      }
+      Err(_) => std::thread::sleep(POLL_INTERVAL),
+    }
+  }
+}
+
+#[derive(serde::Serialize)]
+pub struct BridgeStatusDto {
+  running: bool,
+  listen_port: Option<u16>,
+  active_clients: usize,
+  bytes_forwarded: u64,
+}
+
+#[tauri::command]
+pub async fn bridge_start(
+  manager: tauri::State<'_, Arc<BridgeManager>>,
+  listen_port: u16,
+  serial_port: String,
+  baud_rate: u32,
+  settings: Option<SerialSettingsDto>,
+) -> Result<(), String> {
+  let manager = manager.inner().clone();
+  tauri::async_runtime::spawn_blocking(move || {
+    let mut running = manager.running.lock().unwrap();
+    if running.is_some() {
+      return Err("Bridge is already running; call bridge_stop first".to_string());
+    }
+
+    let port_name = serial_port.clone();
+    let builder = serialport::new(serial_port, baud_rate).timeout(Duration::from_secs(3));
+    let sp = apply_serial_settings(builder, &settings)?
+      .open()
+      .map_err(|e| format!("Unable to open serial port {port_name}: {e}"))?;
+    let serial = Arc::new(Mutex::new(sp));
+
+    let listener = TcpListener::bind(("0.0.0.0", listen_port))
+      .map_err(|e| format!("Unable to bind listen port {listen_port}: {e}"))?;
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    let active_clients = Arc::new(AtomicUsize::new(0));
+    let bytes_forwarded = Arc::new(AtomicU64::new(0));
+
+    let accept_cancel = cancel.clone();
+    let accept_active_clients = active_clients.clone();
+    let accept_bytes_forwarded = bytes_forwarded.clone();
+    std::thread::spawn(move || {
+      run_accept_loop(
+        listener,
+        serial,
+        accept_cancel,
+        accept_active_clients,
+        accept_bytes_forwarded,
+      );
+    });
+
+    *running = Some(RunningBridge {
+      cancel,
+      listen_port,
+      active_clients,
+      bytes_forwarded,
+    });
+
+    Ok(())
+  })
+  .await
+  .map_err(|e| format!("Bridge start task failed: {e}"))?
+}
+
+#[tauri::command]
+pub async fn bridge_stop(manager: tauri::State<'_, Arc<BridgeManager>>) -> Result<(), String> {
+  if let Some(running) = manager.running.lock().unwrap().take() {
+    running.cancel.store(true, Ordering::SeqCst);
+  }
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn bridge_status(
+  manager: tauri::State<'_, Arc<BridgeManager>>,
+) -> Result<BridgeStatusDto, String> {
+  let running = manager.running.lock().unwrap();
+  Ok(match running.as_ref() {
+    Some(running) => BridgeStatusDto {
+      running: true,
+      listen_port: Some(running.listen_port),
+      active_clients: running.active_clients.load(Ordering::SeqCst),
+      bytes_forwarded: running.bytes_forwarded.load(Ordering::SeqCst),
+    },
+    None => BridgeStatusDto {
+      running: false,
+      listen_port: None,
+      active_clients: 0,
+      bytes_forwarded: 0,
+    },
+  })
+}
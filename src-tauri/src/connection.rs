@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serialport::SerialPort;
+
+use crate::{
+  apply_serial_settings, decode_status, read_status_byte, PrinterStatusDto, SerialSettingsDto,
+  DLE_EOT_OFFLINE_STATUS, DLE_EOT_PAPER_SENSOR_STATUS, DLE_EOT_PRINTER_STATUS,
+};
+
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+const KEEPALIVE_TIMEOUT: Duration = Duration::from_millis(500);
+/// Write/read timeout for the persistent connection's actual print jobs,
+/// matching `serial_print_escpos`'s timeout. Kept separate from
+/// `KEEPALIVE_TIMEOUT` so a printer legitimately holding CTS low while
+/// draining its buffer isn't mistaken for a dead connection mid-job.
+const PRINT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A printer connection left open across jobs, holding either a TCP or serial transport.
+enum OpenConnection {
+  Tcp(TcpStream),
+  Serial(Box<dyn SerialPort>),
+}
+
+impl OpenConnection {
+  fn write_all(&mut self, data: &[u8]) -> std::io::Result<()> {
+    match self {
+      OpenConnection::Tcp(stream) => stream.write_all(data),
+      OpenConnection::Serial(port) => {
+        // Same chunked-write pacing used by the one-shot serial commands.
+        for chunk in data.chunks(512) {
+          port.write_all(chunk)?;
+          std::thread::sleep(Duration::from_millis(20));
+        }
+        Ok(())
+      }
+    }
+  }
+
+  fn flush(&mut self) -> std::io::Result<()> {
+    match self {
+      OpenConnection::Tcp(stream) => stream.flush(),
+      OpenConnection::Serial(port) => port.flush(),
+    }
+  }
+
+  fn read_byte(&mut self, deadline: Instant) -> std::io::Result<Option<u8>> {
+    match self {
+      OpenConnection::Tcp(stream) => read_status_byte(stream, deadline),
+      OpenConnection::Serial(port) => read_status_byte(port, deadline),
+    }
+  }
+
+  fn query_status(&mut self) -> Result<PrinterStatusDto, String> {
+    let timeout = Duration::from_secs(3);
+    let mut query = |req: &[u8]| -> Result<Option<u8>, String> {
+      self
+        .write_all(req)
+        .map_err(|e| format!("Write failed: {e}"))?;
+      self.flush().map_err(|e| format!("Flush failed: {e}"))?;
+      self
+        .read_byte(Instant::now() + timeout)
+        .map_err(|e| format!("Read failed: {e}"))
+    };
+
+    let printer_status = query(&DLE_EOT_PRINTER_STATUS)?;
+    let offline_status = query(&DLE_EOT_OFFLINE_STATUS)?;
+    let paper_status = query(&DLE_EOT_PAPER_SENSOR_STATUS)?;
+    Ok(decode_status(printer_status, offline_status, paper_status))
+  }
+
+  /// Lightweight liveness probe for the keepalive thread: a single DLE EOT 1
+  /// read with a short timeout, ignoring the decoded bits entirely.
+  fn keepalive_probe(&mut self) -> std::io::Result<()> {
+    self.write_all(&DLE_EOT_PRINTER_STATUS)?;
+    self.flush()?;
+    if let OpenConnection::Serial(port) = self {
+      let _ = port.set_timeout(KEEPALIVE_TIMEOUT);
+    }
+    let result = self.read_byte(Instant::now() + KEEPALIVE_TIMEOUT);
+    if let OpenConnection::Serial(port) = self {
+      let _ = port.set_timeout(PRINT_TIMEOUT);
+    }
+    result?;
+    Ok(())
+  }
+}
+
+/// Connections are wrapped per-entry so the outer map lock only ever guards
+/// the lookup/insert/remove itself; the actual blocking I/O locks just the
+/// one connection it's using, leaving every other open connection free.
+#[derive(Default)]
+pub struct ConnectionManager {
+  connections: Mutex<HashMap<String, Arc<Mutex<OpenConnection>>>>,
+  next_id: AtomicU64,
+}
+
+impl ConnectionManager {
+  fn next_conn_id(&self) -> String {
+    let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+    format!("conn-{id}")
+  }
+
+  fn get(&self, conn_id: &str) -> Option<Arc<Mutex<OpenConnection>>> {
+    self.connections.lock().unwrap().get(conn_id).cloned()
+  }
+}
+
+/// Starts a background keepalive thread for `manager`; each call spawns a new
+/// thread, so the app calls it once, at setup. Runs for the lifetime of the process.
+pub fn spawn_keepalive(manager: Arc<ConnectionManager>) {
+  std::thread::spawn(move || loop {
+    std::thread::sleep(KEEPALIVE_INTERVAL);
+
+    // Snapshot the handles and release the map lock before probing, so a
+    // slow/unreachable connection only stalls its own handle, not the map.
+    let snapshot: Vec<(String, Arc<Mutex<OpenConnection>>)> = manager
+      .connections
+      .lock()
+      .unwrap()
+      .iter()
+      .map(|(id, conn)| (id.clone(), conn.clone()))
+      .collect();
+
+    let mut dead = Vec::new();
+    for (id, conn) in snapshot {
+      if conn.lock().unwrap().keepalive_probe().is_err() {
+        dead.push(id);
+      }
+    }
+
+    if !dead.is_empty() {
+      let mut connections = manager.connections.lock().unwrap();
+      for id in &dead {
+        connections.remove(id);
+      }
+    }
+  });
+}
+
+#[derive(serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConnTargetDto {
+  Tcp {
+    host: String,
+    port: u16,
+  },
+  Serial {
+    port: String,
+    baud_rate: u32,
+    settings: Option<SerialSettingsDto>,
+  },
+}
+
+#[tauri::command]
+pub async fn printer_open(
+  manager: tauri::State<'_, Arc<ConnectionManager>>,
+  target: ConnTargetDto,
+) -> Result<String, String> {
+  let manager = manager.inner().clone();
+  tauri::async_runtime::spawn_blocking(move || {
+    let conn = match target {
+      ConnTargetDto::Tcp { host, port } => {
+        let addr = (host.as_str(), port)
+          .to_socket_addrs()
+          .map_err(|e| format!("Unable to resolve host: {e}"))?
+          .next()
+          .ok_or("Unable to resolve host")?;
+        let timeout = Duration::from_secs(3);
+        let stream = TcpStream::connect_timeout(&addr, timeout)
+          .map_err(|e| format!("TCP connect failed: {e}"))?;
+        let _ = stream.set_write_timeout(Some(timeout));
+        let _ = stream.set_read_timeout(Some(KEEPALIVE_TIMEOUT));
+        let _ = stream.set_nodelay(true);
+        OpenConnection::Tcp(stream)
+      }
+      ConnTargetDto::Serial {
+        port,
+        baud_rate,
+        settings,
+      } => {
+        let port_name = port.clone();
+        let builder = serialport::new(port, baud_rate).timeout(PRINT_TIMEOUT);
+        let sp = apply_serial_settings(builder, &settings)?
+          .open()
+          .map_err(|e| format!("Unable to open serial port {port_name}: {e}"))?;
+        OpenConnection::Serial(sp)
+      }
+    };
+
+    let conn_id = manager.next_conn_id();
+    manager
+      .connections
+      .lock()
+      .unwrap()
+      .insert(conn_id.clone(), Arc::new(Mutex::new(conn)));
+    Ok(conn_id)
+  })
+  .await
+  .map_err(|e| format!("Open task failed: {e}"))?
+}
+
+#[tauri::command]
+pub async fn printer_write(
+  manager: tauri::State<'_, Arc<ConnectionManager>>,
+  conn_id: String,
+  data: Vec<u8>,
+) -> Result<(), String> {
+  let manager = manager.inner().clone();
+  tauri::async_runtime::spawn_blocking(move || {
+    let conn = manager
+      .get(&conn_id)
+      .ok_or_else(|| format!("No open connection {conn_id}"))?;
+
+    let result = {
+      let mut conn = conn.lock().unwrap();
+      conn
+        .write_all(&data)
+        .and_then(|_| conn.flush())
+        .map_err(|e| format!("Write failed ({conn_id}): {e}"))
+    };
+
+    // Auto-evict the broken connection; later calls with this id fail with
+    // "No open connection" until the caller opens a new one with `printer_open`.
+    if result.is_err() {
+      manager.connections.lock().unwrap().remove(&conn_id);
+    }
+    result
+  })
+  .await
+  .map_err(|e| format!("Write task failed: {e}"))?
+}
+
+#[tauri::command]
+pub async fn printer_query(
+  manager: tauri::State<'_, Arc<ConnectionManager>>,
+  conn_id: String,
+) -> Result<PrinterStatusDto, String> {
+  let manager = manager.inner().clone();
+  tauri::async_runtime::spawn_blocking(move || {
+    let conn = manager
+      .get(&conn_id)
+      .ok_or_else(|| format!("No open connection {conn_id}"))?;
+
+    let result = conn.lock().unwrap().query_status();
+
+    if result.is_err() {
+      manager.connections.lock().unwrap().remove(&conn_id);
+    }
+    result
+  })
+  .await
+  .map_err(|e| format!("Query task failed: {e}"))?
+}
+
+#[tauri::command]
+pub async fn printer_close(
+  manager: tauri::State<'_, Arc<ConnectionManager>>,
+  conn_id: String,
+) -> Result<(), String> {
+  manager.connections.lock().unwrap().remove(&conn_id);
+  Ok(())
+}
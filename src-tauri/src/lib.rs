@@ -1,6 +1,77 @@
-use std::io::Write;
+mod ble;
+mod bridge;
+mod connection;
+
+use std::io::{Read, Write};
 use std::net::{TcpStream, ToSocketAddrs};
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use ble::BleManager;
+use bridge::BridgeManager;
+use connection::ConnectionManager;
+use tauri::Manager;
+
+/// Optional line settings for opening a serial port. Any field left unset
+/// falls back to the 8N1/no-flow-control default every existing caller relies on.
+#[derive(serde::Deserialize, Default)]
+pub(crate) struct SerialSettingsDto {
+  data_bits: Option<u8>,
+  parity: Option<String>,
+  stop_bits: Option<u8>,
+  flow_control: Option<String>,
+}
+
+pub(crate) fn apply_serial_settings(
+  mut builder: serialport::SerialPortBuilder,
+  settings: &Option<SerialSettingsDto>,
+) -> Result<serialport::SerialPortBuilder, String> {
+  let Some(settings) = settings else {
+    return Ok(builder);
+  };
+
+  if let Some(data_bits) = settings.data_bits {
+    builder = builder.data_bits(match data_bits {
+      5 => serialport::DataBits::Five,
+      6 => serialport::DataBits::Six,
+      7 => serialport::DataBits::Seven,
+      8 => serialport::DataBits::Eight,
+      other => return Err(format!("Unsupported data_bits: {other} (expected 5-8)")),
+    });
+  }
+
+  if let Some(parity) = &settings.parity {
+    builder = builder.parity(match parity.to_ascii_lowercase().as_str() {
+      "none" => serialport::Parity::None,
+      "odd" => serialport::Parity::Odd,
+      "even" => serialport::Parity::Even,
+      other => return Err(format!("Unsupported parity: {other} (expected none/odd/even)")),
+    });
+  }
+
+  if let Some(stop_bits) = settings.stop_bits {
+    builder = builder.stop_bits(match stop_bits {
+      1 => serialport::StopBits::One,
+      2 => serialport::StopBits::Two,
+      other => return Err(format!("Unsupported stop_bits: {other} (expected 1 or 2)")),
+    });
+  }
+
+  if let Some(flow_control) = &settings.flow_control {
+    builder = builder.flow_control(match flow_control.to_ascii_lowercase().as_str() {
+      "none" => serialport::FlowControl::None,
+      "software" => serialport::FlowControl::Software,
+      "hardware" => serialport::FlowControl::Hardware,
+      other => {
+        return Err(format!(
+          "Unsupported flow_control: {other} (expected none/software/hardware)"
+        ))
+      }
+    });
+  }
+
+  Ok(builder)
+}
 
 #[derive(serde::Serialize)]
 struct SerialPortDto {
@@ -87,12 +158,154 @@ async fn serial_list_ports() -> Result<Vec<SerialPortDto>, String> {
   .map_err(|e| format!("List ports task failed: {e}"))?
 }
 
+#[derive(serde::Serialize)]
+pub(crate) struct PrinterStatusDto {
+  online: bool,
+  cover_open: bool,
+  paper_out: bool,
+  feed_button: bool,
+  error: bool,
+}
+
+/// Real-time status transmission requests (DLE EOT n), per the ESC/POS spec.
+pub(crate) const DLE_EOT_PRINTER_STATUS: [u8; 3] = [0x10, 0x04, 0x01];
+pub(crate) const DLE_EOT_OFFLINE_STATUS: [u8; 3] = [0x10, 0x04, 0x02];
+pub(crate) const DLE_EOT_PAPER_SENSOR_STATUS: [u8; 3] = [0x10, 0x04, 0x04];
+
+pub(crate) fn bit_set(byte: u8, n: u8) -> bool {
+  (byte >> n) & 1 != 0
+}
+
+/// Reads a single reply byte, retrying short/partial reads until `deadline`
+/// elapses. Returns `Ok(None)` on timeout so callers can report `online: false`
+/// instead of surfacing a timeout as a hard connection error.
+pub(crate) fn read_status_byte(
+  read: &mut impl Read,
+  deadline: Instant,
+) -> Result<Option<u8>, std::io::Error> {
+  let mut byte = [0u8; 1];
+  loop {
+    match read.read(&mut byte) {
+      Ok(1) => return Ok(Some(byte[0])),
+      Ok(0) => return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "connection closed")),
+      Err(e) if e.kind() == std::io::ErrorKind::WouldBlock || e.kind() == std::io::ErrorKind::TimedOut => {}
+      Err(e) => return Err(e),
+      Ok(_) => unreachable!(),
+    }
+
+    if Instant::now() >= deadline {
+      return Ok(None);
+    }
+  }
+}
+
+pub(crate) fn decode_status(
+  printer_status: Option<u8>,
+  offline_status: Option<u8>,
+  paper_status: Option<u8>,
+) -> PrinterStatusDto {
+  let (printer_status, offline_status, paper_status) =
+    match (printer_status, offline_status, paper_status) {
+      (Some(p), Some(o), Some(s)) => (p, o, s),
+      _ => {
+        return PrinterStatusDto {
+          online: false,
+          cover_open: false,
+          paper_out: false,
+          feed_button: false,
+          error: false,
+        }
+      }
+    };
+
+  PrinterStatusDto {
+    online: !bit_set(printer_status, 3),
+    cover_open: bit_set(offline_status, 2),
+    paper_out: bit_set(paper_status, 5) || bit_set(paper_status, 6),
+    feed_button: bit_set(offline_status, 3),
+    error: bit_set(offline_status, 5),
+  }
+}
+
+#[tauri::command]
+async fn tcp_query_escpos_status(host: String, port: u16) -> Result<PrinterStatusDto, String> {
+  tauri::async_runtime::spawn_blocking(move || {
+    let addr = (host.as_str(), port)
+      .to_socket_addrs()
+      .map_err(|e| format!("Unable to resolve host: {e}"))?
+      .next()
+      .ok_or("Unable to resolve host")?;
+
+    let timeout = Duration::from_secs(3);
+    let mut stream =
+      TcpStream::connect_timeout(&addr, timeout).map_err(|e| format!("TCP connect failed: {e}"))?;
+    let _ = stream.set_write_timeout(Some(timeout));
+    let _ = stream.set_read_timeout(Some(Duration::from_millis(100)));
+    let _ = stream.set_nodelay(true);
+
+    let mut query = |req: &[u8]| -> Result<Option<u8>, String> {
+      stream
+        .write_all(req)
+        .map_err(|e| format!("TCP write failed: {e}"))?;
+      stream.flush().map_err(|e| format!("TCP flush failed: {e}"))?;
+      read_status_byte(&mut stream, Instant::now() + timeout)
+        .map_err(|e| format!("TCP read failed: {e}"))
+    };
+
+    let printer_status = query(&DLE_EOT_PRINTER_STATUS)?;
+    let offline_status = query(&DLE_EOT_OFFLINE_STATUS)?;
+    let paper_status = query(&DLE_EOT_PAPER_SENSOR_STATUS)?;
+
+    Ok(decode_status(printer_status, offline_status, paper_status))
+  })
+  .await
+  .map_err(|e| format!("Status query task failed: {e}"))?
+}
+
 #[tauri::command]
-async fn serial_print_escpos(port: String, baud_rate: u32, data: Vec<u8>) -> Result<(), String> {
+async fn serial_query_escpos_status(
+  port: String,
+  baud_rate: u32,
+  settings: Option<SerialSettingsDto>,
+) -> Result<PrinterStatusDto, String> {
   tauri::async_runtime::spawn_blocking(move || {
     let port_name = port.clone();
-    let mut sp = serialport::new(port, baud_rate)
-      .timeout(Duration::from_secs(3))
+    let timeout = Duration::from_secs(3);
+    let builder = serialport::new(port, baud_rate).timeout(Duration::from_millis(100));
+    let mut sp = apply_serial_settings(builder, &settings)?
+      .open()
+      .map_err(|e| format!("Unable to open serial port {port_name}: {e}"))?;
+
+    let mut query = |req: &[u8]| -> Result<Option<u8>, String> {
+      sp.write_all(req)
+        .map_err(|e| format!("Serial write failed ({port_name}): {e}"))?;
+      sp.flush()
+        .map_err(|e| format!("Serial flush failed ({port_name}): {e}"))?;
+      read_status_byte(&mut sp, Instant::now() + timeout)
+        .map_err(|e| format!("Serial read failed ({port_name}): {e}"))
+    };
+
+    let printer_status = query(&DLE_EOT_PRINTER_STATUS)?;
+    let offline_status = query(&DLE_EOT_OFFLINE_STATUS)?;
+    let paper_status = query(&DLE_EOT_PAPER_SENSOR_STATUS)?;
+
+    Ok(decode_status(printer_status, offline_status, paper_status))
+  })
+  .await
+  .map_err(|e| format!("Status query task failed: {e}"))?
+}
+
+#[tauri::command]
+async fn serial_print_escpos(
+  port: String,
+  baud_rate: u32,
+  data: Vec<u8>,
+  settings: Option<SerialSettingsDto>,
+) -> Result<(), String> {
+  tauri::async_runtime::spawn_blocking(move || {
+    let port_name = port.clone();
+    let builder = serialport::new(port, baud_rate).timeout(Duration::from_secs(3));
+    let mut sp = apply_serial_settings(builder, &settings)?
       .open()
       .map_err(|e| format!("Unable to open serial port {port_name}: {e}"))?;
 
@@ -111,13 +324,132 @@ async fn serial_print_escpos(port: String, baud_rate: u32, data: Vec<u8>) -> Res
   .map_err(|e| format!("Print task failed: {e}"))?
 }
 
+#[derive(serde::Serialize)]
+struct LoopbackResultDto {
+  bytes_sent: usize,
+  bytes_received: usize,
+  duration_ms: u128,
+  mean_latency_ms: f64,
+  worst_latency_ms: f64,
+  passed: bool,
+}
+
+/// Fills `len` bytes with a repeating, easily-recognisable pattern so a
+/// mismatched loopback read is obvious in logs.
+fn default_loopback_pattern(len: usize) -> Vec<u8> {
+  (0..len).map(|i| (i % 256) as u8).collect()
+}
+
+#[tauri::command]
+async fn serial_loopback_test(
+  port: String,
+  baud_rate: u32,
+  payload_len: usize,
+  iterations: u32,
+  raw_bytes: Option<Vec<u8>>,
+  split: Option<bool>,
+  settings: Option<SerialSettingsDto>,
+) -> Result<LoopbackResultDto, String> {
+  tauri::async_runtime::spawn_blocking(move || {
+    let port_name = port.clone();
+    let builder = serialport::new(port, baud_rate).timeout(Duration::from_secs(3));
+    let mut sp = apply_serial_settings(builder, &settings)?
+      .open()
+      .map_err(|e| format!("Unable to open serial port {port_name}: {e}"))?;
+
+    let pattern = raw_bytes.unwrap_or_else(|| default_loopback_pattern(payload_len));
+    let split = split.unwrap_or(false);
+
+    let mut bytes_sent = 0usize;
+    let mut bytes_received = 0usize;
+    let mut latencies = Vec::with_capacity(iterations as usize);
+    let mut mismatched = false;
+    let start = Instant::now();
+
+    for _ in 0..iterations {
+      let round_start = Instant::now();
+      let received = if split {
+        // Read on a spawned thread while writing on this one, so
+        // full-duplex adapters actually get exercised both ways at once.
+        let mut reader = sp
+          .try_clone()
+          .map_err(|e| format!("Unable to clone serial port ({port_name}): {e}"))?;
+        let expected_len = pattern.len();
+        let reader_handle = std::thread::spawn(move || -> Result<Vec<u8>, std::io::Error> {
+          let mut buf = vec![0u8; expected_len];
+          reader.read_exact(&mut buf)?;
+          Ok(buf)
+        });
+
+        sp.write_all(&pattern)
+          .map_err(|e| format!("Serial write failed ({port_name}): {e}"))?;
+        sp.flush()
+          .map_err(|e| format!("Serial flush failed ({port_name}): {e}"))?;
+
+        reader_handle
+          .join()
+          .map_err(|_| format!("Loopback reader thread panicked ({port_name})"))?
+          .map_err(|e| format!("Serial read failed ({port_name}): {e}"))?
+      } else {
+        sp.write_all(&pattern)
+          .map_err(|e| format!("Serial write failed ({port_name}): {e}"))?;
+        sp.flush()
+          .map_err(|e| format!("Serial flush failed ({port_name}): {e}"))?;
+
+        let mut buf = vec![0u8; pattern.len()];
+        sp.read_exact(&mut buf)
+          .map_err(|e| format!("Serial read failed ({port_name}): {e}"))?;
+        buf
+      };
+
+      latencies.push(round_start.elapsed().as_secs_f64() * 1000.0);
+      bytes_sent += pattern.len();
+      bytes_received += received.len();
+      if received != pattern {
+        mismatched = true;
+      }
+    }
+
+    let mean_latency_ms = if latencies.is_empty() {
+      0.0
+    } else {
+      latencies.iter().sum::<f64>() / latencies.len() as f64
+    };
+    let worst_latency_ms = latencies.iter().cloned().fold(0.0, f64::max);
+
+    Ok(LoopbackResultDto {
+      bytes_sent,
+      bytes_received,
+      duration_ms: start.elapsed().as_millis(),
+      mean_latency_ms,
+      worst_latency_ms,
+      passed: !mismatched && bytes_sent == bytes_received,
+    })
+  })
+  .await
+  .map_err(|e| format!("Loopback task failed: {e}"))?
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
   tauri::Builder::default()
     .invoke_handler(tauri::generate_handler![
       tcp_print_escpos,
+      tcp_query_escpos_status,
       serial_list_ports,
-      serial_print_escpos
+      serial_print_escpos,
+      serial_query_escpos_status,
+      serial_loopback_test,
+      connection::printer_open,
+      connection::printer_write,
+      connection::printer_query,
+      connection::printer_close,
+      bridge::bridge_start,
+      bridge::bridge_stop,
+      bridge::bridge_status,
+      ble::ble_scan,
+      ble::ble_connect,
+      ble::ble_print_escpos
     ])
     .setup(|app| {
       if cfg!(debug_assertions) {
@@ -127,8 +459,143 @@ pub fn run() {
             .build(),
         )?;
       }
+
+      let manager = Arc::new(ConnectionManager::default());
+      connection::spawn_keepalive(manager.clone());
+      app.manage(manager);
+      app.manage(Arc::new(BridgeManager::default()));
+      app.manage(Arc::new(BleManager::default()));
+
       Ok(())
     })
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn decode_status_all_bits_clear_reports_healthy_printer() {
+    let status = decode_status(Some(0b0000_0000), Some(0b0000_0000), Some(0b0000_0000));
+    assert!(status.online);
+    assert!(!status.cover_open);
+    assert!(!status.paper_out);
+    assert!(!status.feed_button);
+    assert!(!status.error);
+  }
+
+  #[test]
+  fn decode_status_offline_bit_flips_online() {
+    let status = decode_status(Some(1 << 3), Some(0), Some(0));
+    assert!(!status.online);
+  }
+
+  #[test]
+  fn decode_status_cover_open_is_offline_status_bit_2() {
+    let status = decode_status(Some(0), Some(1 << 2), Some(0));
+    assert!(status.cover_open);
+  }
+
+  #[test]
+  fn decode_status_error_is_offline_status_bit_5() {
+    let status = decode_status(Some(0), Some(1 << 5), Some(0));
+    assert!(status.error);
+  }
+
+  #[test]
+  fn decode_status_feed_button_is_offline_status_bit_3() {
+    let status = decode_status(Some(0), Some(1 << 3), Some(0));
+    assert!(status.feed_button);
+  }
+
+  #[test]
+  fn decode_status_paper_out_is_paper_sensor_bit_5_or_6() {
+    assert!(decode_status(Some(0), Some(0), Some(1 << 5)).paper_out);
+    assert!(decode_status(Some(0), Some(0), Some(1 << 6)).paper_out);
+    assert!(!decode_status(Some(0), Some(0), Some(1 << 4)).paper_out);
+  }
+
+  #[test]
+  fn decode_status_missing_reply_reports_offline() {
+    let status = decode_status(None, Some(0), Some(0));
+    assert!(!status.online);
+    assert!(!status.error);
+  }
+
+  #[test]
+  fn apply_serial_settings_none_is_a_no_op() {
+    let builder = serialport::new("/dev/null", 9600);
+    assert!(apply_serial_settings(builder, &None).is_ok());
+  }
+
+  #[test]
+  fn apply_serial_settings_rejects_bad_data_bits() {
+    let builder = serialport::new("/dev/null", 9600);
+    let settings = Some(SerialSettingsDto {
+      data_bits: Some(9),
+      parity: None,
+      stop_bits: None,
+      flow_control: None,
+    });
+    assert!(apply_serial_settings(builder, &settings).is_err());
+  }
+
+  #[test]
+  fn apply_serial_settings_rejects_bad_parity() {
+    let builder = serialport::new("/dev/null", 9600);
+    let settings = Some(SerialSettingsDto {
+      data_bits: None,
+      parity: Some("weird".to_string()),
+      stop_bits: None,
+      flow_control: None,
+    });
+    assert!(apply_serial_settings(builder, &settings).is_err());
+  }
+
+  #[test]
+  fn apply_serial_settings_rejects_bad_stop_bits() {
+    let builder = serialport::new("/dev/null", 9600);
+    let settings = Some(SerialSettingsDto {
+      data_bits: None,
+      parity: None,
+      stop_bits: Some(3),
+      flow_control: None,
+    });
+    assert!(apply_serial_settings(builder, &settings).is_err());
+  }
+
+  #[test]
+  fn apply_serial_settings_rejects_bad_flow_control() {
+    let builder = serialport::new("/dev/null", 9600);
+    let settings = Some(SerialSettingsDto {
+      data_bits: None,
+      parity: None,
+      stop_bits: None,
+      flow_control: Some("carrier-pigeon".to_string()),
+    });
+    assert!(apply_serial_settings(builder, &settings).is_err());
+  }
+
+  #[test]
+  fn apply_serial_settings_accepts_valid_7e2_hardware_flow_settings() {
+    let builder = serialport::new("/dev/null", 9600);
+    let settings = Some(SerialSettingsDto {
+      data_bits: Some(7),
+      parity: Some("even".to_string()),
+      stop_bits: Some(2),
+      flow_control: Some("hardware".to_string()),
+    });
+    assert!(apply_serial_settings(builder, &settings).is_ok());
+  }
+
+  #[test]
+  fn default_loopback_pattern_has_requested_length_and_repeating_bytes() {
+    let pattern = default_loopback_pattern(258);
+    assert_eq!(pattern.len(), 258);
+    assert_eq!(pattern[0], 0);
+    assert_eq!(pattern[255], 255);
+    assert_eq!(pattern[256], 0);
+  }
+}